@@ -0,0 +1,509 @@
+//! Spawning and supervising the Node "bridge" sidecar process.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use shared_child::SharedChild;
+use tauri::{App, AppHandle, Emitter, Manager, State};
+
+use crate::bridge_error::{BridgeError, SpawnAttempt};
+use crate::bridge_log::{LogBuffer, LogSource};
+
+/// Grace period `bridge_stop`/shutdown give the bridge to exit after a
+/// polite signal before resorting to a hard kill.
+const STOP_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Initial delay before the first restart attempt after a crash.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+/// Backoff doubles on each consecutive failure, capped here.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Uptime after which a restart is considered "stable" and backoff resets.
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+/// Give up supervising after this many consecutive failed restarts.
+const MAX_RETRIES: u32 = 10;
+/// How long `bridge_request` waits for a matching reply before giving up.
+const RPC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Send SIGTERM to the bridge's whole process group on Unix (it's spawned as
+/// a group leader via `process_group(0)` in `try_spawn`), or kill just the
+/// leader elsewhere. Killing only the leader pid lets a multi-process
+/// strategy like `pnpm --prefix bridge dev` leave its `node` child running.
+fn signal_group(child: &SharedChild) {
+  #[cfg(unix)]
+  unsafe {
+    libc::kill(-(child.id() as i32), libc::SIGTERM);
+  }
+  #[cfg(not(unix))]
+  let _ = child.kill();
+}
+
+/// Shared handle to the currently running bridge child, if any.
+///
+/// The child itself is wrapped in `shared_child::SharedChild` so the
+/// supervisor thread can block in `wait()` while `bridge_write` concurrently
+/// grabs `stdin` from the side without racing on the same lock.
+pub struct BridgeProcess {
+  child: Mutex<Option<Arc<SharedChild>>>,
+  stdin: Mutex<Option<ChildStdin>>,
+  /// Set just before a deliberate (non-crash) kill, e.g. for hot-reload, so
+  /// the supervisor can skip backoff and the crash retry count for that exit
+  /// (it still counts toward `bridge_status`'s `restarts`).
+  intentional_restart: AtomicBool,
+  /// Monotonic id source for `bridge_request` correlation.
+  next_request_id: AtomicU64,
+  /// Replies awaited by `bridge_request`, keyed by the id they were sent
+  /// with. Failed out with `BridgeError::NotAvailable` when the child they
+  /// were sent to exits, instead of leaving the caller blocked for the full
+  /// `RPC_TIMEOUT`.
+  pending: Mutex<HashMap<u64, Sender<Result<JsonValue, BridgeError>>>>,
+  /// Recent stdout/stderr lines, structured and ring-buffered for backfill.
+  pub(crate) logs: LogBuffer,
+  /// When the currently running child was spawned, for `bridge_status` uptime.
+  started_at: Mutex<Option<Instant>>,
+  /// Total number of times the bridge has been (re)spawned, including the
+  /// first spawn. `bridge_status` reports `restarts` as this minus one.
+  spawn_count: AtomicU32,
+  /// Set by `bridge_stop` so the supervisor parks instead of respawning
+  /// after the child exits, until `bridge_restart` clears it.
+  stop_requested: AtomicBool,
+}
+
+impl BridgeProcess {
+  fn empty() -> Self {
+    BridgeProcess {
+      child: Mutex::new(None),
+      stdin: Mutex::new(None),
+      intentional_restart: AtomicBool::new(false),
+      next_request_id: AtomicU64::new(1),
+      pending: Mutex::new(HashMap::new()),
+      logs: LogBuffer::new(),
+      started_at: Mutex::new(None),
+      spawn_count: AtomicU32::new(0),
+      stop_requested: AtomicBool::new(false),
+    }
+  }
+
+  /// Record a (re)spawn and return the 1-based spawn number, so the caller
+  /// can tell the very first spawn apart from a later restart.
+  fn set(&self, child: Arc<SharedChild>, stdin: Option<ChildStdin>) -> u32 {
+    *self.child.lock().unwrap() = Some(child);
+    *self.stdin.lock().unwrap() = stdin;
+    *self.started_at.lock().unwrap() = Some(Instant::now());
+    self.spawn_count.fetch_add(1, Ordering::SeqCst) + 1
+  }
+
+  fn clear(&self) {
+    *self.child.lock().unwrap() = None;
+    *self.stdin.lock().unwrap() = None;
+    *self.started_at.lock().unwrap() = None;
+  }
+
+  /// Fail every `bridge_request` still waiting on a reply, so a crash or
+  /// restart doesn't leave callers blocked until `RPC_TIMEOUT` for a reply
+  /// that will never come from the child they sent it to.
+  fn fail_pending(&self) {
+    for (_, sender) in self.pending.lock().unwrap().drain() {
+      let _ = sender.send(Err(BridgeError::NotAvailable));
+    }
+  }
+
+  /// Kill the running bridge so the supervisor respawns it right away,
+  /// without treating the exit as a crash. Used by the dev hot-reload
+  /// watcher and to resume after `bridge_stop`.
+  ///
+  /// `intentional_restart` is only set when there's a live child to kill:
+  /// if it were set unconditionally (e.g. while parked after `bridge_stop`,
+  /// or mid-backoff with no child), it would have nothing to consume it at
+  /// spawn time and would linger to silently misclassify the *next* genuine
+  /// crash as an intentional restart.
+  pub(crate) fn request_restart(&self) {
+    self.stop_requested.store(false, Ordering::SeqCst);
+    if let Some(child) = self.child.lock().unwrap().as_ref() {
+      self.intentional_restart.store(true, Ordering::SeqCst);
+      // Signal the whole process group, not just `child.kill()`: the bridge
+      // is spawned as a group leader (`process_group(0)` in `try_spawn`), and
+      // a strategy like `pnpm --prefix bridge dev` has its own `node` child.
+      // SIGKILL'ing only the leader orphans that child, which then keeps the
+      // old bridge's stdin/stdout around while the supervisor spawns a
+      // second one.
+      signal_group(child);
+    }
+  }
+
+  /// Ask the bridge to exit (closing stdin, then SIGTERM on Unix), wait up
+  /// to `STOP_GRACE_PERIOD` for it to do so, then kill it outright. Marks
+  /// the supervisor as stopped so it won't respawn until `request_restart`.
+  pub(crate) fn stop(&self) {
+    self.stop_requested.store(true, Ordering::SeqCst);
+    self.kill_gracefully();
+  }
+
+  /// Stop the bridge on app exit, the same graceful way as `stop` (so app
+  /// exit can block for up to `STOP_GRACE_PERIOD`). Sets `stop_requested` first so
+  /// the supervisor parks instead of respawning: the supervisor thread keeps
+  /// running until the app process actually exits, so without this a
+  /// respawn racing app teardown can leave an orphaned bridge behind.
+  pub(crate) fn shutdown(&self) {
+    self.stop_requested.store(true, Ordering::SeqCst);
+    self.kill_gracefully();
+  }
+
+  fn kill_gracefully(&self) {
+    let Some(child) = self.child.lock().unwrap().clone() else { return };
+
+    // Closing stdin is the signal our bridge protocol treats as "shut down".
+    *self.stdin.lock().unwrap() = None;
+
+    signal_group(&child);
+
+    let deadline = Instant::now() + STOP_GRACE_PERIOD;
+    while !matches!(child.try_wait(), Ok(Some(_))) {
+      if Instant::now() >= deadline {
+        #[cfg(unix)]
+        unsafe {
+          libc::kill(-(child.id() as i32), libc::SIGKILL);
+        }
+        let _ = child.kill();
+        break;
+      }
+      std::thread::sleep(Duration::from_millis(50));
+    }
+  }
+}
+
+/// Snapshot of the bridge's lifecycle, returned by `bridge_status`.
+#[derive(Serialize)]
+pub struct BridgeStatus {
+  pub running: bool,
+  pub pid: Option<u32>,
+  pub uptime_secs: Option<u64>,
+  pub restarts: u32,
+}
+
+#[tauri::command]
+pub fn bridge_status(state: State<'_, Arc<BridgeProcess>>) -> BridgeStatus {
+  let child = state.child.lock().unwrap();
+  let started_at = state.started_at.lock().unwrap();
+  BridgeStatus {
+    running: child.is_some(),
+    pid: child.as_ref().map(|c| c.id()),
+    uptime_secs: started_at.map(|t| t.elapsed().as_secs()),
+    restarts: state.spawn_count.load(Ordering::SeqCst).saturating_sub(1),
+  }
+}
+
+/// Restart the bridge right away (e.g. after `bridge_stop`), without waiting
+/// out the crash backoff. Only a running child or a supervisor parked by
+/// `bridge_stop` is restarted: while the supervisor sleeps out a crash
+/// backoff the respawn still waits for it, and after it has given up
+/// nothing is respawned.
+#[tauri::command]
+pub fn bridge_restart(state: State<'_, Arc<BridgeProcess>>) {
+  state.request_restart();
+}
+
+/// Gracefully stop the bridge and keep it stopped until `bridge_restart`.
+#[tauri::command]
+pub fn bridge_stop(app_handle: AppHandle, state: State<'_, Arc<BridgeProcess>>) {
+  state.stop();
+  let _ = app_handle.emit("bridge-stopped", ());
+}
+
+#[tauri::command]
+pub fn bridge_write(data: String, state: State<'_, Arc<BridgeProcess>>) -> Result<(), BridgeError> {
+  if state.child.lock().unwrap().is_none() {
+    return Err(BridgeError::NotAvailable);
+  }
+  let mut guard = state.stdin.lock().unwrap();
+  let stdin = guard.as_mut().ok_or(BridgeError::StdinMissing)?;
+  stdin.write_all(data.as_bytes())?;
+  stdin.write_all(b"\n")?;
+  stdin.flush()?;
+  Ok(())
+}
+
+/// Send `method`/`params` to the bridge and wait for the reply carrying the
+/// same request id, so callers get a proper request/response round trip
+/// instead of firing into stdin and listening for unrelated stdout events.
+#[tauri::command]
+pub fn bridge_request(
+  method: String,
+  params: JsonValue,
+  state: State<'_, Arc<BridgeProcess>>,
+) -> Result<JsonValue, BridgeError> {
+  if state.child.lock().unwrap().is_none() {
+    return Err(BridgeError::NotAvailable);
+  }
+
+  let id = state.next_request_id.fetch_add(1, Ordering::SeqCst);
+  let (tx, rx) = mpsc::channel();
+  state.pending.lock().unwrap().insert(id, tx);
+
+  let line = format!("{}\n", serde_json::json!({ "id": id, "method": method, "params": params }));
+  let write_result: Result<(), BridgeError> = (|| {
+    let mut guard = state.stdin.lock().unwrap();
+    let stdin = guard.as_mut().ok_or(BridgeError::StdinMissing)?;
+    stdin.write_all(line.as_bytes())?;
+    stdin.flush()?;
+    Ok(())
+  })();
+  if let Err(e) = write_result {
+    state.pending.lock().unwrap().remove(&id);
+    return Err(e);
+  }
+
+  match rx.recv_timeout(RPC_TIMEOUT) {
+    Ok(result) => result,
+    Err(_) => {
+      state.pending.lock().unwrap().remove(&id);
+      Err(BridgeError::Timeout)
+    }
+  }
+}
+
+/// Try to spawn program with args and return Child or a `SpawnAttempt`
+/// recording why it didn't work, for the caller to accumulate.
+fn try_spawn(program: &str, args: &[&str]) -> Result<Child, SpawnAttempt> {
+  eprintln!("tauri: attempting to spawn: {} {}", program, args.join(" "));
+  let mut cmd = Command::new(program);
+  for a in args { cmd.arg(a); }
+  cmd.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped());
+
+  // Make the bridge its own process group leader so a graceful/hard kill
+  // can target the whole group (e.g. pnpm's own child processes) rather
+  // than just this one pid.
+  #[cfg(unix)]
+  {
+    use std::os::unix::process::CommandExt;
+    cmd.process_group(0);
+  }
+
+  cmd.spawn().map_err(|e| SpawnAttempt {
+    program: format!("{} {}", program, args.join(" ")),
+    reason: e.to_string(),
+  })
+}
+
+/// Resolve candidate bridge paths and try spawn strategies in order.
+/// Returns Ok(child) if any strategy succeeds, otherwise a `BridgeError`
+/// listing every candidate that was attempted.
+fn spawn_bridge_process() -> Result<Child, BridgeError> {
+  let mut attempts = Vec::new();
+
+  // 1) BRIDGE_DEV_CMD override
+  if let Ok(cmdline) = std::env::var("BRIDGE_DEV_CMD") {
+    let parts: Vec<&str> = cmdline.split_whitespace().collect();
+    if !parts.is_empty() {
+      let prog = parts[0];
+      let args: Vec<&str> = parts.iter().skip(1).copied().collect();
+      match try_spawn(prog, &args) {
+        Ok(c) => return Ok(c),
+        Err(a) => attempts.push(a),
+      }
+    }
+  }
+
+  // 2) Prefer built JS in ./bridge/dist/index.js (inside project)
+  let cand_local = Path::new("bridge").join("dist").join("index.js");
+  if cand_local.exists() {
+    if let Ok(abs) = cand_local.canonicalize() {
+      match try_spawn("node", &[abs.to_str().unwrap()]) {
+        Ok(c) => return Ok(c),
+        Err(a) => attempts.push(a),
+      }
+    }
+  }
+
+  // 3) Check sibling ../bridge/dist/index.js (if bridge lives outside)
+  let cand_parent = Path::new("..").join("bridge").join("dist").join("index.js");
+  if cand_parent.exists() {
+    if let Ok(abs) = cand_parent.canonicalize() {
+      match try_spawn("node", &[abs.to_str().unwrap()]) {
+        Ok(c) => return Ok(c),
+        Err(a) => attempts.push(a),
+      }
+    }
+  }
+
+  // 4) Try pnpm --prefix bridge dev (windows aware)
+  #[cfg(target_os = "windows")]
+  {
+    // Use cmd /C to run a composite command in windows shell context
+    match try_spawn("cmd", &["/C", "pnpm", "--prefix", "bridge", "dev"]) {
+      Ok(c) => return Ok(c),
+      Err(a) => attempts.push(a),
+    }
+    // also try parent prefix
+    match try_spawn("cmd", &["/C", "pnpm", "--prefix", "..\\bridge", "dev"]) {
+      Ok(c) => return Ok(c),
+      Err(a) => attempts.push(a),
+    }
+  }
+  #[cfg(not(target_os = "windows"))]
+  {
+    match try_spawn("pnpm", &["--prefix", "bridge", "dev"]) {
+      Ok(c) => return Ok(c),
+      Err(a) => attempts.push(a),
+    }
+    match try_spawn("pnpm", &["--prefix", "../bridge", "dev"]) {
+      Ok(c) => return Ok(c),
+      Err(a) => attempts.push(a),
+    }
+  }
+
+  Err(BridgeError::SpawnFailed { attempts })
+}
+
+/// Spawn the bridge, wire its stdout/stderr to events, and wrap it in a
+/// `SharedChild` so the supervisor can wait on it concurrently with writes.
+fn spawn_and_wire(
+  app_handle: &AppHandle,
+  state: &Arc<BridgeProcess>,
+) -> Result<(Arc<SharedChild>, Option<ChildStdin>), BridgeError> {
+  let mut child = spawn_bridge_process()?;
+  eprintln!("tauri: spawned bridge pid {}", child.id());
+
+  let stdin = child.stdin.take();
+  let stdout = child.stdout.take();
+  let stderr = child.stderr.take();
+
+  let shared = Arc::new(SharedChild::new(child)?);
+
+  if let Some(stdout) = stdout {
+    let ah = app_handle.clone();
+    let state = state.clone();
+    std::thread::spawn(move || {
+      let reader = BufReader::new(stdout);
+      for line in reader.lines().flatten() {
+        let routed = serde_json::from_str::<JsonValue>(&line).ok().and_then(|value| {
+          let id = value.get("id")?.as_u64()?;
+          let sender = state.pending.lock().unwrap().remove(&id)?;
+          let _ = sender.send(Ok(value));
+          Some(())
+        });
+        if routed.is_none() {
+          let event = state.logs.ingest(LogSource::Stdout, &line);
+          let _ = ah.emit("bridge-log", event);
+        }
+      }
+    });
+  }
+
+  if let Some(stderr) = stderr {
+    let ah = app_handle.clone();
+    let state = state.clone();
+    std::thread::spawn(move || {
+      let reader = BufReader::new(stderr);
+      for line in reader.lines().flatten() {
+        let event = state.logs.ingest(LogSource::Stderr, &line);
+        let _ = ah.emit("bridge-log", event);
+      }
+    });
+  }
+
+  Ok((shared, stdin))
+}
+
+/// Keep the bridge running: spawn it, wait for it to exit, and respawn with
+/// exponential backoff until it stabilizes or `MAX_RETRIES` is exceeded.
+fn supervise(app_handle: AppHandle, state: Arc<BridgeProcess>) {
+  let mut backoff = INITIAL_BACKOFF;
+  let mut failures = 0u32;
+
+  loop {
+    if state.stop_requested.load(Ordering::SeqCst) {
+      // A `bridge_stop` landed in the gap between children (crash backoff,
+      // or before the first spawn) rather than while one was running.
+      // `kill_gracefully` had nothing to kill, so park here ourselves
+      // instead of spawning a fresh child out from under the stop request.
+      while state.stop_requested.load(Ordering::SeqCst) {
+        std::thread::sleep(Duration::from_millis(250));
+      }
+      backoff = INITIAL_BACKOFF;
+      failures = 0;
+      continue;
+    }
+
+    match spawn_and_wire(&app_handle, &state) {
+      Ok((shared, stdin)) => {
+        let spawn_number = state.set(shared.clone(), stdin);
+        // The very first spawn isn't a "restart" from the frontend's point of
+        // view; only emit that event for spawns after it.
+        let event = if spawn_number == 1 { "bridge-started" } else { "bridge-restarted" };
+        let _ = app_handle.emit(event, ());
+
+        let started_at = Instant::now();
+        let status = shared.wait();
+        state.clear();
+        eprintln!("tauri: bridge exited: {:?}", status);
+        // Any `bridge_request` still waiting on this child will never get a
+        // reply; fail it now instead of making the caller sit out the full
+        // RPC timeout.
+        state.fail_pending();
+
+        if state.stop_requested.load(Ordering::SeqCst) {
+          // Deliberate stop (`bridge_stop`): park here, not counting this as
+          // a crash, until `bridge_restart` resumes the supervisor.
+          while state.stop_requested.load(Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(250));
+          }
+          backoff = INITIAL_BACKOFF;
+          failures = 0;
+          continue;
+        }
+
+        if state.intentional_restart.swap(false, Ordering::SeqCst) {
+          // Deliberate restart (e.g. hot-reload): respawn immediately and
+          // don't let it count against the crash backoff/retry budget.
+          backoff = INITIAL_BACKOFF;
+          failures = 0;
+          continue;
+        }
+
+        let _ = app_handle.emit(
+          "bridge-crashed",
+          status.map(|s| s.to_string()).unwrap_or_else(|e| e.to_string()),
+        );
+
+        if started_at.elapsed() >= STABLE_UPTIME {
+          backoff = INITIAL_BACKOFF;
+          failures = 0;
+        } else {
+          failures += 1;
+        }
+      }
+      Err(e) => {
+        eprintln!("ERROR: could not start bridge: {}", e);
+        failures += 1;
+      }
+    }
+
+    if failures > MAX_RETRIES {
+      eprintln!("tauri: bridge exceeded max restart attempts ({}), giving up", MAX_RETRIES);
+      let _ = app_handle.emit("bridge-gave-up", ());
+      break;
+    }
+
+    std::thread::sleep(backoff);
+    backoff = (backoff * 2).min(MAX_BACKOFF);
+  }
+}
+
+/// Start the bridge and its supervisor thread, managing `BridgeProcess` state
+/// on the app so commands can reach it immediately (even before the first
+/// spawn attempt completes).
+pub fn init(app: &App) {
+  let state = Arc::new(BridgeProcess::empty());
+  app.manage(state.clone());
+
+  let handle = app.handle().clone();
+  std::thread::spawn(move || supervise(handle, state));
+}
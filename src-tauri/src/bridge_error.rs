@@ -0,0 +1,57 @@
+//! Error type shared across the bridge's spawn, write, and RPC paths.
+//!
+//! Plain `Result<_, String>` loses the error's kind, so the frontend can
+//! only ever show the message and never branch on *why* something failed.
+//! `BridgeError` carries that kind through to JS as a structured object.
+
+use std::io;
+
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+/// One candidate the spawn-strategy loop tried, and why it didn't work.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpawnAttempt {
+  pub program: String,
+  pub reason: String,
+}
+
+#[derive(Debug, Error)]
+pub enum BridgeError {
+  #[error("bridge not available")]
+  NotAvailable,
+
+  #[error("bridge stdin missing")]
+  StdinMissing,
+
+  #[error("failed to spawn bridge, tried: {}", attempts.iter().map(|a| format!("{} ({})", a.program, a.reason)).collect::<Vec<_>>().join(", "))]
+  SpawnFailed { attempts: Vec<SpawnAttempt> },
+
+  #[error("failed to write to bridge stdin: {0}")]
+  WriteFailed(#[from] io::Error),
+
+  #[error("bridge request timed out")]
+  Timeout,
+}
+
+impl Serialize for BridgeError {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeStruct;
+    let mut out = serializer.serialize_struct("BridgeError", 2)?;
+    out.serialize_field("kind", self.kind())?;
+    out.serialize_field("message", &self.to_string())?;
+    out.end()
+  }
+}
+
+impl BridgeError {
+  fn kind(&self) -> &'static str {
+    match self {
+      BridgeError::NotAvailable => "notAvailable",
+      BridgeError::StdinMissing => "stdinMissing",
+      BridgeError::SpawnFailed { .. } => "spawnFailed",
+      BridgeError::WriteFailed(_) => "writeFailed",
+      BridgeError::Timeout => "timeout",
+    }
+  }
+}
@@ -0,0 +1,113 @@
+//! Structured log events forwarded from the bridge's stdout/stderr, backed
+//! by a bounded ring buffer so newly opened windows can backfill.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::bridge::BridgeProcess;
+
+/// How many log entries to retain for `bridge_log_tail` backfill.
+const CAPACITY: usize = 1000;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+  Info,
+  Warn,
+  Error,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogSource {
+  Stdout,
+  Stderr,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BridgeLogEvent {
+  pub level: LogLevel,
+  pub message: String,
+  /// Timestamp carried by the bridge itself, if the line was structured.
+  pub timestamp: Option<String>,
+  pub source: LogSource,
+  /// Monotonic sequence number assigned at ingestion, for stable ordering.
+  pub seq: u64,
+  /// Milliseconds since the Unix epoch when this line was ingested.
+  pub ingested_at_ms: u128,
+}
+
+/// Shape of a recognized structured log line emitted by the bridge, e.g.
+/// `{"level":"warn","msg":"retrying","ts":"2026-07-26T00:00:00Z"}`.
+#[derive(Deserialize)]
+struct RawLogLine {
+  level: LogLevel,
+  msg: String,
+  #[serde(default)]
+  ts: Option<String>,
+}
+
+/// Bounded ring buffer of recent bridge log events.
+pub struct LogBuffer {
+  entries: Mutex<VecDeque<BridgeLogEvent>>,
+  next_seq: AtomicU64,
+}
+
+impl LogBuffer {
+  pub fn new() -> Self {
+    LogBuffer { entries: Mutex::new(VecDeque::with_capacity(CAPACITY)), next_seq: AtomicU64::new(0) }
+  }
+
+  /// Parse a raw stdout/stderr line into a `BridgeLogEvent`, append it to the
+  /// ring buffer, and return it for the caller to emit to the renderer.
+  ///
+  /// Lines that already look like `{"level":...,"msg":...}` keep their own
+  /// level; anything else is classified by which stream it came from.
+  pub fn ingest(&self, source: LogSource, raw: &str) -> BridgeLogEvent {
+    let (level, message, timestamp) = match serde_json::from_str::<RawLogLine>(raw) {
+      Ok(parsed) => (parsed.level, parsed.msg, parsed.ts),
+      Err(_) => {
+        let level = match source {
+          LogSource::Stdout => LogLevel::Info,
+          LogSource::Stderr => LogLevel::Warn,
+        };
+        (level, raw.to_string(), None)
+      }
+    };
+
+    let event = BridgeLogEvent {
+      level,
+      message,
+      timestamp,
+      source,
+      seq: self.next_seq.fetch_add(1, Ordering::SeqCst),
+      ingested_at_ms: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis(),
+    };
+
+    let mut entries = self.entries.lock().unwrap();
+    if entries.len() >= CAPACITY {
+      entries.pop_front();
+    }
+    entries.push_back(event.clone());
+
+    event
+  }
+
+  /// Return the last `n` log entries, oldest first.
+  pub fn tail(&self, n: usize) -> Vec<BridgeLogEvent> {
+    let entries = self.entries.lock().unwrap();
+    entries.iter().rev().take(n).rev().cloned().collect()
+  }
+}
+
+/// Return the last `n` buffered bridge log entries, so a newly opened window
+/// can backfill its console instead of starting blank.
+#[tauri::command]
+pub fn bridge_log_tail(n: usize, state: State<'_, Arc<BridgeProcess>>) -> Vec<BridgeLogEvent> {
+  state.logs.tail(n)
+}
@@ -0,0 +1,97 @@
+//! Dev-only file watcher that hot-reloads the bridge sidecar when its
+//! source changes, so you don't have to restart the whole Tauri app while
+//! working on the bridge. Writes under `bridge/dist` and
+//! `bridge/node_modules` are ignored (see `IGNORED_DIRS`), so a change to
+//! the built output alone does not trigger a reload.
+//!
+//! Only wired up in debug builds: release builds ship a fixed bridge build
+//! and have no use for this.
+#![cfg(debug_assertions)]
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use notify::{RecursiveMode, Watcher};
+use tauri::{App, AppHandle, Emitter, Manager};
+
+use crate::bridge::BridgeProcess;
+
+/// Events are debounced by this much before triggering a reload, so a burst
+/// of writes (e.g. a bundler rewriting several files at once) reloads only
+/// once.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Start watching `bridge/` for changes, if the app is actually using a dev
+/// bridge (a `BRIDGE_DEV_CMD` override or a local `bridge/dist/index.js`).
+pub fn init(app: &App) {
+  let using_dev_bridge =
+    std::env::var("BRIDGE_DEV_CMD").is_ok() || Path::new("bridge").join("dist").join("index.js").exists();
+  if !using_dev_bridge {
+    return;
+  }
+
+  let watch_dir = Path::new("bridge").to_path_buf();
+  if !watch_dir.exists() {
+    return;
+  }
+
+  let handle = app.handle().clone();
+  std::thread::spawn(move || watch(handle, watch_dir));
+}
+
+/// Directories under `bridge/` whose writes should never trigger a reload:
+/// both are the *output* of the dev command we're watching for, so treating
+/// them as source changes is a feedback loop (build writes to `dist` ->
+/// reload -> bridge restarts -> build writes to `dist` -> ...).
+const IGNORED_DIRS: [&str; 2] = ["dist", "node_modules"];
+
+fn is_ignored(path: &Path) -> bool {
+  path.components().any(|c| matches!(c.as_os_str().to_str(), Some(name) if IGNORED_DIRS.contains(&name)))
+}
+
+/// Whether an event is a source change worth reloading for, i.e. it touches
+/// at least one path outside `IGNORED_DIRS`.
+fn is_relevant(event: &notify::Result<notify::Event>) -> bool {
+  match event {
+    Ok(event) => event.paths.iter().any(|p| !is_ignored(p)),
+    Err(_) => true,
+  }
+}
+
+fn watch(app_handle: AppHandle, dir: PathBuf) {
+  let (tx, rx) = std::sync::mpsc::channel();
+  let mut watcher = match notify::recommended_watcher(tx) {
+    Ok(w) => w,
+    Err(e) => {
+      eprintln!("tauri: failed to start bridge watcher: {}", e);
+      return;
+    }
+  };
+
+  if let Err(e) = watcher.watch(&dir, RecursiveMode::Recursive) {
+    eprintln!("tauri: failed to watch '{}': {}", dir.display(), e);
+    return;
+  }
+
+  eprintln!("tauri: watching '{}' for bridge hot-reload", dir.display());
+
+  loop {
+    // Block for the first relevant event, ignoring the bridge's own build
+    // output, then drain and debounce whatever follows.
+    loop {
+      match rx.recv() {
+        Ok(event) if is_relevant(&event) => break,
+        Ok(_) => continue,
+        Err(_) => return,
+      }
+    }
+    while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+      let _ = event;
+    }
+
+    eprintln!("tauri: bridge source changed, reloading");
+    let _ = app_handle.emit("bridge-reloading", ());
+    app_handle.state::<Arc<BridgeProcess>>().request_restart();
+  }
+}